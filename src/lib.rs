@@ -1,11 +1,13 @@
 pub mod state;
 pub mod stateeither;
 pub mod stateresult;
+pub mod statet;
 pub mod nomparser;
 
 pub mod prelude {
   pub use crate::state::*;
   pub use crate::stateeither::*;
   pub use crate::stateresult::*;
+  pub use crate::statet::{EitherInner, Identity, MonadInner, MonadThrow, ResultInner, StateT};
   pub use crate::nomparser::*;
 }
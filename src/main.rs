@@ -54,6 +54,7 @@ mod nom_example {
   use nom::{
     bytes::complete::{tag, take_while_m_n},
     combinator::map_res,
+    error::VerboseError,
     sequence::tuple,
     IResult,
   };
@@ -81,6 +82,35 @@ mod nom_example {
     map_res(take_while_m_n(2, 2, is_hex_digit), from_hex)(input)
   }
 
+  fn hex_primary_verbose(input: &str) -> IResult<&str, u8, VerboseError<&str>> {
+    map_res(take_while_m_n(2, 2, is_hex_digit), from_hex)(input)
+  }
+
+  // Streaming counterpart of `hex_primary`: same digits, but via `nom::bytes::streaming`, so
+  // running short asks for more input (`Err::Incomplete`) instead of failing outright. Errors
+  // are `()` rather than `nom::error::Error<&str>` because `Streaming::feed` reparses against a
+  // fresh buffer view on every resumption, so the error type can't borrow from it.
+  fn hex_primary_streaming(input: &str) -> IResult<&str, u8, ()> {
+    map_res(
+      nom::bytes::streaming::take_while_m_n(2, 2, is_hex_digit),
+      from_hex,
+    )(input)
+  }
+
+  // Streaming counterpart of `hex_color_parser`, built the same way but over
+  // `nom::bytes::streaming::tag` so a truncated "#2F" asks [`run_streaming`]/[`Streaming::feed`]
+  // for more input instead of erroring. A plain `fn` (no captured state), since that's what
+  // [`run_streaming`]/[`Streaming::feed`] need their `parse` to be.
+  fn hex_color_parser_streaming(buffer: &String) -> IResult<&str, Color, ()> {
+    let ma = m! {
+      nomp(nom::bytes::streaming::tag("#"));
+      x <- nomp(tuple((hex_primary_streaming, hex_primary_streaming, hex_primary_streaming)));
+      let (red, green, blue) = x;
+      <_>::lift(Color { red, green, blue })
+    };
+    run_nomparser(buffer.view(), ma)
+  }
+
   #[allow(dead_code)]
   // Alternative approach.
   fn hex_primary2(input: &str) -> IResult<&str, u8> {
@@ -100,6 +130,45 @@ mod nom_example {
     }
   }
 
+  // Same shape as `hex_color_parser`, but over `VerboseError` and with the triple wrapped in
+  // `context`, so a bad RGB triple after the '#' is labeled instead of just reporting a bare
+  // `ErrorKind::TakeWhileMN`.
+  fn hex_color_parser_with_context<'a>() -> NomParser<'a, &'a str, Color, VerboseError<&'a str>> {
+    m! {
+      nomp(tag("#"));
+      x <- context(
+        "expected RGB triple after '#'",
+        nomp(tuple((hex_primary_verbose, hex_primary_verbose, hex_primary_verbose))),
+      );
+      let (red, green, blue) = x;
+      <_>::lift(Color { red, green, blue })
+    }
+  }
+
+  fn named_color_parser<'a>() -> NompStr<'a, Color> {
+    alt_p(vec![
+      nomp(tag("red")).map(|_| Color { red: 0xFF, green: 0x00, blue: 0x00 }),
+      nomp(tag("green")).map(|_| Color { red: 0x00, green: 0xFF, blue: 0x00 }),
+      nomp(tag("blue")).map(|_| Color { red: 0x00, green: 0x00, blue: 0xFF }),
+    ])
+  }
+
+  // Falls back to the hex triple when the input isn't one of the names above.
+  fn color_parser<'a>() -> NompStr<'a, Color> {
+    named_color_parser().or(hex_color_parser())
+  }
+
+  // Comma-separated hex primaries, e.g. "2F,14,DF", as called out in the `separated_list0`
+  // rationale.
+  fn hex_primary_list_parser<'a>() -> NompStr<'a, Vec<u8>> {
+    separated_list0(|| nomp(tag(",")), || nomp(hex_primary))
+  }
+
+  // A run of concatenated hex primaries with no separator, e.g. "2F14DF".
+  fn hex_bytes_parser<'a>() -> NompStr<'a, Vec<u8>> {
+    many0(|| nomp(hex_primary))
+  }
+
   fn hex_primary_parser<'a>() -> NompStr<'a, u8> {
     nomp(map_res(take_while_m_n(2, 2, is_hex_digit), from_hex))
   }
@@ -125,6 +194,37 @@ mod nom_example {
     x.push_str("#2F14DF");
     let result = run_nomparser(x.as_str(), hex_color_parser());
     println!("Result: {:?}", result);
+
+    // `context` labeling a bad RGB triple.
+    let result = run_nomparser("#2F1", hex_color_parser_with_context());
+    println!("Result: {:?}", result);
+
+    // `or`/`alt_p` falling back from the named colors to the hex parser.
+    let result = run_nomparser("green", color_parser());
+    println!("Result: {:?}", result);
+    let result = run_nomparser("#2F14DF", color_parser());
+    println!("Result: {:?}", result);
+
+    // `separated_list0`/`many0` reusing `hex_primary` to parse several primaries at once.
+    let result = run_nomparser("2F,14,DF", hex_primary_list_parser());
+    println!("Result: {:?}", result);
+    let result = run_nomparser("2F14DF", hex_bytes_parser());
+    println!("Result: {:?}", result);
+
+    // Streaming: feed the hex-color parser a truncated "#2F", get told to come back with
+    // more, then supply the rest in a second `feed` -- the same `hex_color_parser_streaming`
+    // shape as `hex_color_parser` above, just resumable across chunks of `&str` input.
+    match run_streaming::<String, Color, ()>(String::from("#2F"), hex_color_parser_streaming) {
+      StreamResult::Incomplete(streaming) => match streaming.feed("14DF") {
+        StreamResult::Done(rest, color) => println!("Result: Ok((\"{}\", {:?}))", rest, color),
+        StreamResult::Error(e) => println!("Result: Err({:?})", e),
+        StreamResult::Incomplete(_) => println!("Result: still incomplete after feeding"),
+      },
+      StreamResult::Done(rest, color) => {
+        println!("Result: Ok((\"{}\", {:?})) (unexpectedly done already)", rest, color)
+      }
+      StreamResult::Error(e) => println!("Result: Err({:?})", e),
+    }
   }
 }
 
@@ -1,5 +1,8 @@
 use do_notation::Lift;
-use nom::{error::Error, IResult};
+use nom::{
+  error::{ContextError, Error, ErrorKind, ParseError},
+  InputLength, IResult,
+};
 
 pub struct NomParser<'a, S, A, E = Error<S>>(Box<dyn FnOnce(S) -> IResult<S, A, E> + 'a>);
 
@@ -24,6 +27,51 @@ impl<'a, S, A, E> NomParser<'a, S, A, E> {
       Err(e) => Err(e),
     }))
   }
+
+  // Tries `self` first; if it fails with a recoverable `nom::Err::Error`, re-parses the
+  // original input with `other`. Failures and incompletes short-circuit without trying `other`.
+  pub fn or(self, other: NomParser<'a, S, A, E>) -> NomParser<'a, S, A, E>
+  where
+    S: Clone + 'a,
+    A: 'a,
+    E: 'a,
+  {
+    NomParser(Box::new(move |s: S| match self.0(s.clone()) {
+      Err(nom::Err::Error(_)) => other.0(s),
+      result => result,
+    }))
+  }
+
+  // Implemented directly rather than via `self.and_then(|a| Self::lift(f(a)))`, so it
+  // doesn't allocate a second boxed closure on top of the one `and_then` already needs.
+  pub fn map<B, F>(self, f: F) -> NomParser<'a, S, B, E>
+  where
+    F: FnOnce(A) -> B + 'a,
+    A: 'a,
+    E: 'a,
+    S: 'a,
+  {
+    NomParser(Box::new(move |s| match self.0(s) {
+      Ok((rest, a)) => Ok((rest, f(a))),
+      Err(e) => Err(e),
+    }))
+  }
+
+  // Runs `self` then `other` in sequence and pairs up their results. Built directly on the
+  // underlying `IResult`, not on `and_then`.
+  pub fn zip2<B>(self, other: NomParser<'a, S, B, E>) -> NomParser<'a, S, (A, B), E>
+  where
+    A: 'a,
+    B: 'a,
+    E: 'a,
+    S: 'a,
+  {
+    NomParser(Box::new(move |s| {
+      let (rest, a) = self.0(s)?;
+      let (rest, b) = other.0(rest)?;
+      Ok((rest, (a, b)))
+    }))
+  }
 }
 
 pub fn nomp<'a, F, S, A, E>(f: F) -> NomParser<'a, S, A, E>
@@ -33,17 +81,312 @@ where
   NomParser(Box::new(f))
 }
 
+// Like nom's `alt`, but for `NomParser`: tries each alternative in order, keeping the
+// first success and only falling through on recoverable `nom::Err::Error`s. An empty
+// `parsers` is a normal parse failure, not a panic, matching `many1`/`separated_list0`'s
+// no-panic treatment of the zero-match case.
+pub fn alt_p<'a, S, A, E>(parsers: Vec<NomParser<'a, S, A, E>>) -> NomParser<'a, S, A, E>
+where
+  S: Clone + 'a,
+  A: 'a,
+  E: 'a + ParseError<S>,
+{
+  let mut iter = parsers.into_iter();
+  match iter.next() {
+    Some(first) => iter.fold(first, |acc, p| acc.or(p)),
+    None => NomParser(Box::new(|s: S| {
+      Err(nom::Err::Error(E::from_error_kind(s, ErrorKind::Alt)))
+    })),
+  }
+}
+
 pub fn run_nomparser<S, A, E>(s: S, ma: NomParser<S, A, E>) -> IResult<S, A, E> {
   ma.0(s)
 }
 
+/// Runs a vector of parsers left-to-right against the same input, threading the remainder
+/// through each, and collects their results into a single parser producing a `Vec`.
+pub fn sequence<'a, S, A, E>(mas: Vec<NomParser<'a, S, A, E>>) -> NomParser<'a, S, Vec<A>, E>
+where
+  S: 'a,
+  A: 'a,
+  E: 'a,
+{
+  mas.into_iter().fold(NomParser::lift(Vec::new()), |acc, ma| {
+    acc.zip2(ma).map(|(mut v, a)| {
+      v.push(a);
+      v
+    })
+  })
+}
+
+/// Maps `f` over `items` and [`sequence`]s the resulting parsers.
+pub fn traverse<'a, S, T, A, E, F>(items: Vec<T>, f: F) -> NomParser<'a, S, Vec<A>, E>
+where
+  S: 'a,
+  T: 'a,
+  A: 'a,
+  E: 'a,
+  F: Fn(T) -> NomParser<'a, S, A, E>,
+{
+  sequence(items.into_iter().map(f).collect())
+}
+
+// A `NomParser` is single-shot (its `Box<dyn FnOnce>` is consumed after one run), so
+// repetition needs a *factory* that produces a fresh parser for every iteration.
+fn many0_with<'a, S, A, E, F>(item: &F, input: S) -> IResult<S, Vec<A>, E>
+where
+  S: Clone + InputLength,
+  E: ParseError<S>,
+  F: Fn() -> NomParser<'a, S, A, E>,
+{
+  let mut acc = Vec::new();
+  let mut rest = input;
+  loop {
+    let len_before = rest.input_len();
+    match item().0(rest.clone()) {
+      Ok((next, a)) => {
+        // Guard against the classic nom infinite-loop bug: a parser that succeeds without
+        // consuming any input would otherwise make this loop run forever.
+        if next.input_len() == len_before {
+          return Err(nom::Err::Error(E::from_error_kind(rest, ErrorKind::Many0)));
+        }
+        acc.push(a);
+        rest = next;
+      }
+      Err(nom::Err::Error(_)) => return Ok((rest, acc)),
+      Err(e) => return Err(e),
+    }
+  }
+}
+
+/// Applies `item` (a parser factory, so it can be re-run) zero or more times, stopping at
+/// the first recoverable failure and returning the unconsumed input.
+pub fn many0<'a, S, A, E, F>(item: F) -> NomParser<'a, S, Vec<A>, E>
+where
+  S: Clone + InputLength + 'a,
+  A: 'a,
+  E: 'a + ParseError<S>,
+  F: Fn() -> NomParser<'a, S, A, E> + 'a,
+{
+  NomParser(Box::new(move |input| many0_with(&item, input)))
+}
+
+/// Like [`many0`], but requires at least one match, erroring out if `item` doesn't match
+/// even once.
+pub fn many1<'a, S, A, E, F>(item: F) -> NomParser<'a, S, Vec<A>, E>
+where
+  S: Clone + InputLength + 'a,
+  A: 'a,
+  E: 'a + ParseError<S>,
+  F: Fn() -> NomParser<'a, S, A, E> + 'a,
+{
+  NomParser(Box::new(move |input: S| match item().0(input.clone()) {
+    Err(nom::Err::Error(_)) => Err(nom::Err::Error(E::from_error_kind(input, ErrorKind::Many1))),
+    Err(e) => Err(e),
+    Ok((rest, first)) => many0_with(&item, rest).map(|(rest, tail)| {
+      let mut acc = vec![first];
+      acc.extend(tail);
+      (rest, acc)
+    }),
+  }))
+}
+
+/// Parses zero or more `item`s separated by `sep`, discarding the separators. Like
+/// [`many0`]/[`many1`], `sep` and `item` are factories producing a fresh parser per use.
+pub fn separated_list0<'a, S, SEP, A, E, FS, FI>(sep: FS, item: FI) -> NomParser<'a, S, Vec<A>, E>
+where
+  S: Clone + InputLength + 'a,
+  SEP: 'a,
+  A: 'a,
+  E: 'a + ParseError<S>,
+  FS: Fn() -> NomParser<'a, S, SEP, E> + 'a,
+  FI: Fn() -> NomParser<'a, S, A, E> + 'a,
+{
+  NomParser(Box::new(move |input: S| match item().0(input.clone()) {
+    Err(nom::Err::Error(_)) => Ok((input, Vec::new())),
+    Err(e) => Err(e),
+    Ok((mut rest, first)) => {
+      let mut acc = vec![first];
+      loop {
+        let len_before = rest.input_len();
+        match sep().0(rest.clone()) {
+          Err(nom::Err::Error(_)) => return Ok((rest, acc)),
+          Err(e) => return Err(e),
+          Ok((after_sep, _)) => match item().0(after_sep.clone()) {
+            Err(nom::Err::Error(_)) => return Ok((rest, acc)),
+            Err(e) => return Err(e),
+            Ok((next, a)) => {
+              if next.input_len() == len_before {
+                return Err(nom::Err::Error(E::from_error_kind(
+                  rest,
+                  ErrorKind::SeparatedList,
+                )));
+              }
+              acc.push(a);
+              rest = next;
+            }
+          },
+        }
+      }
+    }
+  }))
+}
+
+/// Wraps a recoverable `nom::Err::Error`/`Err::Failure` with a `&'static str` label, like
+/// nom's own `context` combinator, so do-notation parsers can produce messages such as
+/// "expected RGB triple after '#'". Passes `Ok` and `Err::Incomplete` through unchanged.
+pub fn context<'a, S, A, E>(
+  label: &'static str,
+  parser: NomParser<'a, S, A, E>,
+) -> NomParser<'a, S, A, E>
+where
+  S: Clone + 'a,
+  A: 'a,
+  E: 'a + ContextError<S>,
+{
+  NomParser(Box::new(move |s: S| match parser.0(s.clone()) {
+    Err(nom::Err::Error(e)) => Err(nom::Err::Error(E::add_context(s, label, e))),
+    Err(nom::Err::Failure(e)) => Err(nom::Err::Failure(E::add_context(s, label, e))),
+    result => result,
+  }))
+}
+
+/// An owned, growable buffer that can lend itself out as borrowed parser input on demand.
+/// This is what lets [`Streaming`] support borrowed input types like `&str` (the type every
+/// parser in the `nom_example` color parser uses), not just types that already are the
+/// parser's own owned input (like the `Vec<u8>`-driven parser in the tests below): the
+/// `Streaming` holds the owned `Buffer`, and re-borrows a fresh [`Buffer::View`] from it for
+/// every (re-)attempt, since the previous attempt's `NomParser` already consumed the last one.
+pub trait Buffer {
+  type View<'b>
+  where
+    Self: 'b;
+
+  /// Borrows the buffer's current contents as parser input.
+  fn view(&self) -> Self::View<'_>;
+
+  /// Appends freshly-fed input to the buffer.
+  fn push(&mut self, more: Self::View<'_>);
+
+  /// Splits off and returns everything from `consumed` onward, leaving the (no-longer-needed)
+  /// prefix behind in `self`.
+  fn split_off(&mut self, consumed: usize) -> Self;
+}
+
+impl Buffer for String {
+  type View<'b> = &'b str;
+
+  fn view(&self) -> &str {
+    self.as_str()
+  }
+
+  fn push(&mut self, more: &str) {
+    self.push_str(more);
+  }
+
+  fn split_off(&mut self, consumed: usize) -> Self {
+    String::split_off(self, consumed)
+  }
+}
+
+impl Buffer for Vec<u8> {
+  type View<'b> = &'b [u8];
+
+  fn view(&self) -> &[u8] {
+    self.as_slice()
+  }
+
+  fn push(&mut self, more: &[u8]) {
+    self.extend_from_slice(more);
+  }
+
+  fn split_off(&mut self, consumed: usize) -> Self {
+    Vec::split_off(self, consumed)
+  }
+}
+
+/// The result of running (or resuming) a streaming parse: either finished, still waiting
+/// on more input, or a recoverable/unrecoverable parse failure.
+pub enum StreamResult<B: Buffer, A, E> {
+  Done(B, A),
+  Incomplete(Streaming<B, A, E>),
+  Error(nom::Err<E>),
+}
+
+/// A parse attempt that hit `Err::Incomplete`: the input buffered so far, plus the
+/// (re-runnable) `parse` function needed to retry once more data arrives. `parse` takes `&B`
+/// rather than a `Buffer::View` directly and borrows its own view out of it (typically via
+/// [`Buffer::view`]) -- a plain reference argument is what lets a single first-class `fn`
+/// pointer stay generic over the fresh lifetime of every attempt's borrow; a GAT type hidden
+/// purely in the return position can't do that.
+///
+/// `parse` must be a plain, non-capturing `fn`, not a boxed closure: anything that needs to
+/// close over state should build that state into `B`'s buffered input instead. `E` must not
+/// itself borrow from the buffer (hence `'static`): each resumption reparses against a fresh,
+/// differently-lived [`Buffer::View`], so an error type like `nom::error::Error<&str>` (which
+/// borrows its position out of that view) can't be threaded back out. Pick an owned error
+/// type instead -- `()`, `String`, or the like.
+pub struct Streaming<B: Buffer, A, E> {
+  parse: for<'b> fn(&'b B) -> IResult<B::View<'b>, A, E>,
+  buffer: B,
+}
+
+impl<B: Buffer, A, E: 'static> Streaming<B, A, E> {
+  /// Appends `more` to the buffered input and retries `parse` against the whole buffer.
+  pub fn feed(mut self, more: B::View<'_>) -> StreamResult<B, A, E>
+  where
+    for<'b> B::View<'b>: InputLength,
+  {
+    self.buffer.push(more);
+    run_stream_step(self.parse, self.buffer)
+  }
+}
+
+fn run_stream_step<B, A, E>(
+  parse: for<'b> fn(&'b B) -> IResult<B::View<'b>, A, E>,
+  mut buffer: B,
+) -> StreamResult<B, A, E>
+where
+  B: Buffer,
+  for<'b> B::View<'b>: InputLength,
+  E: 'static,
+{
+  let len_before = buffer.view().input_len();
+  // Resolved into a plain, non-borrowing `Option` before touching `buffer` again, so the
+  // borrow `parse` takes doesn't get extended across the `split_off`/move below.
+  let done = match parse(&buffer) {
+    Ok((rest, a)) => Some((len_before - rest.input_len(), a)),
+    Err(nom::Err::Incomplete(_)) => None,
+    Err(e) => return StreamResult::Error(e),
+  };
+  match done {
+    Some((consumed, a)) => StreamResult::Done(buffer.split_off(consumed), a),
+    None => StreamResult::Incomplete(Streaming { parse, buffer }),
+  }
+}
+
+/// Entry point for streaming parses: runs `parse` once against `input`, ready to be
+/// [`Streaming::feed`]-resumed if it comes back `Err::Incomplete`.
+pub fn run_streaming<B, A, E>(
+  input: B,
+  parse: for<'b> fn(&'b B) -> IResult<B::View<'b>, A, E>,
+) -> StreamResult<B, A, E>
+where
+  B: Buffer,
+  for<'b> B::View<'b>: InputLength,
+  E: 'static,
+{
+  run_stream_step(parse, input)
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
   use do_notation::m;
   use nom::{
     bytes::complete::tag,
-    error::{Error, ErrorKind},
+    error::{Error, ErrorKind, VerboseError, VerboseErrorKind},
     Finish,
   };
 
@@ -82,4 +425,186 @@ mod tests {
       result
     );
   }
+
+  #[test]
+  fn or_falls_through_on_error() {
+    let ma: NomParser<_, _> = ptag("hi").or(ptag("bye"));
+    assert_eq!(Ok(("there", "hi")), run_nomparser("hithere", ma));
+    let ma: NomParser<_, _> = ptag("hi").or(ptag("bye"));
+    assert_eq!(Ok(("there", "bye")), run_nomparser("byethere", ma));
+  }
+
+  #[test]
+  fn alt_p_tries_in_order() {
+    let ma: NomParser<_, _> = alt_p(vec![ptag("hi"), ptag("bye"), ptag("yo")]);
+    assert_eq!(Ok(("there", "yo")), run_nomparser("yothere", ma));
+  }
+
+  #[test]
+  fn alt_p_empty_is_a_parse_error_not_a_panic() {
+    let ma: NomParser<&str, &str> = alt_p(Vec::new());
+    assert!(run_nomparser("hithere", ma).finish().is_err());
+  }
+
+  #[test]
+  fn map_transforms_result() {
+    let ma: NomParser<_, _> = ptag("hi").map(str::len);
+    assert_eq!(Ok(("there", 2)), run_nomparser("hithere", ma));
+  }
+
+  #[test]
+  fn zip2_pairs_results() {
+    let ma: NomParser<_, _> = ptag("hi").zip2(ptag("the"));
+    assert_eq!(Ok(("re", ("hi", "the"))), run_nomparser("hithere", ma));
+  }
+
+  #[test]
+  fn sequence_collects_in_order() {
+    let ma: NomParser<_, _> = sequence(vec![ptag("hi"), ptag("the"), ptag("re")]);
+    assert_eq!(Ok(("", vec!["hi", "the", "re"])), run_nomparser("hithere", ma));
+  }
+
+  #[test]
+  fn traverse_maps_then_sequences() {
+    let ma: NomParser<_, _> = traverse(vec!["hi", "the", "re"], ptag);
+    assert_eq!(Ok(("", vec!["hi", "the", "re"])), run_nomparser("hithere", ma));
+  }
+
+  #[test]
+  fn many0_collects_until_mismatch() {
+    let ma: NomParser<_, _> = many0(|| ptag("ab"));
+    assert_eq!(Ok(("c", vec!["ab", "ab"])), run_nomparser("ababc", ma));
+  }
+
+  #[test]
+  fn many0_allows_zero_matches() {
+    let ma: NomParser<_, _> = many0(|| ptag("ab"));
+    assert_eq!(Ok(("c", Vec::<&str>::new())), run_nomparser("c", ma));
+  }
+
+  // Always succeeds without consuming any input, i.e. exactly the shape that would send a
+  // naive repetition loop into an infinite spin if the non-advancing-match guard were gone.
+  fn zero_width<'a>() -> NomParser<'a, &'a str, ()> {
+    nomp(|s: &str| Ok((s, ())))
+  }
+
+  #[test]
+  fn many0_aborts_instead_of_looping_on_zero_width_match() {
+    let ma: NomParser<_, _> = many0(zero_width);
+    assert!(run_nomparser("abc", ma).finish().is_err());
+  }
+
+  #[test]
+  fn many1_aborts_instead_of_looping_on_zero_width_match() {
+    let ma: NomParser<_, _> = many1(zero_width);
+    assert!(run_nomparser("abc", ma).finish().is_err());
+  }
+
+  #[test]
+  fn separated_list0_aborts_instead_of_looping_on_zero_width_pair() {
+    // Both the separator and the item are zero-width, so after the first (real) item the
+    // loop's (sep, item) step makes no progress at all -- exactly what the length check
+    // guards against.
+    let ma: NomParser<_, _> = separated_list0(zero_width, zero_width);
+    assert!(run_nomparser("abc", ma).finish().is_err());
+  }
+
+  #[test]
+  fn many1_requires_at_least_one_match() {
+    let ma: NomParser<_, _> = many1(|| ptag("ab"));
+    assert_eq!(Ok(("c", vec!["ab", "ab"])), run_nomparser("ababc", ma));
+    let ma: NomParser<_, _> = many1(|| ptag("ab"));
+    assert!(run_nomparser("c", ma).finish().is_err());
+  }
+
+  #[test]
+  fn separated_list0_collects_items_between_separators() {
+    let ma: NomParser<_, _> = separated_list0(|| ptag(","), || ptag("ab"));
+    assert_eq!(Ok(("", vec!["ab", "ab", "ab"])), run_nomparser("ab,ab,ab", ma));
+  }
+
+  #[test]
+  fn separated_list0_allows_zero_items() {
+    let ma: NomParser<_, _> = separated_list0(|| ptag(","), || ptag("ab"));
+    assert_eq!(Ok(("c", Vec::<&str>::new())), run_nomparser("c", ma));
+  }
+
+  fn vtag(t: &'static str) -> NomParser<'static, &'static str, &'static str, VerboseError<&'static str>> {
+    nomp(tag(t))
+  }
+
+  #[test]
+  fn context_labels_recoverable_errors() {
+    let ma = context("expected hi", vtag("hi"));
+    match run_nomparser("bye", ma) {
+      Err(nom::Err::Error(e)) => assert!(e
+        .errors
+        .iter()
+        .any(|(_, kind)| matches!(kind, VerboseErrorKind::Context("expected hi")))),
+      other => panic!("expected a labeled error, got {:?}", other),
+    }
+  }
+
+  #[test]
+  fn context_passes_through_success() {
+    let ma = context("expected hi", vtag("hi"));
+    assert_eq!(Ok(("there", "hi")), run_nomparser("hithere", ma));
+  }
+
+  // Takes exactly 4 bytes, or `Incomplete` if fewer are available. A plain `fn` over `&Vec<u8>`
+  // (no captured state), since that's what streaming's `parse` needs -- see `Streaming`'s doc
+  // comment.
+  fn take_4(buffer: &Vec<u8>) -> IResult<&[u8], Vec<u8>, ()> {
+    let input = buffer.view();
+    if input.len() < 4 {
+      Err(nom::Err::Incomplete(nom::Needed::new(4 - input.len())))
+    } else {
+      let (taken, rest) = input.split_at(4);
+      Ok((rest, taken.to_vec()))
+    }
+  }
+
+  #[test]
+  fn streaming_feed_resumes_after_incomplete() {
+    let streaming = match run_streaming::<Vec<u8>, Vec<u8>, ()>(vec![1, 2], take_4) {
+      StreamResult::Incomplete(streaming) => streaming,
+      _ => panic!("expected an incomplete parse"),
+    };
+    match streaming.feed(&[3, 4, 5]) {
+      StreamResult::Done(rest, taken) => {
+        assert_eq!(vec![1, 2, 3, 4], taken);
+        assert_eq!(vec![5], rest);
+      }
+      _ => panic!("expected a completed parse"),
+    }
+  }
+
+  // Same idea as `take_4`, but over `&String`/`&str`, to show streaming also works with the
+  // borrowed input type `nom_example`'s color parser uses.
+  fn take_8_str(buffer: &String) -> IResult<&str, String, ()> {
+    let input = buffer.view();
+    if input.len() < 8 {
+      Err(nom::Err::Incomplete(nom::Needed::new(8 - input.len())))
+    } else {
+      let (taken, rest) = input.split_at(8);
+      Ok((rest, taken.to_string()))
+    }
+  }
+
+  #[test]
+  fn streaming_feed_resumes_over_borrowed_str_input() {
+    // `String` is the `Buffer` impl that lets streaming interoperate with `&str`-driven
+    // parsers like the ones in `nom_example`, not just owned types like `Vec<u8>` above.
+    let streaming = match run_streaming::<String, String, ()>(String::from("hi "), take_8_str) {
+      StreamResult::Incomplete(streaming) => streaming,
+      _ => panic!("expected an incomplete parse"),
+    };
+    match streaming.feed("there!") {
+      StreamResult::Done(rest, taken) => {
+        assert_eq!("hi there", taken);
+        assert_eq!("!", rest);
+      }
+      _ => panic!("expected a completed parse"),
+    }
+  }
 }
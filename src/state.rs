@@ -1,38 +1,17 @@
-use do_notation::Lift;
+use crate::statet::{self, Identity, StateT};
 
-pub struct State<'a, S, A>(Box<dyn FnOnce(S) -> (A, S) + 'a>);
-
-impl<'a, S, A: 'a> Lift<A> for State<'a, S, A> {
-  fn lift(a: A) -> Self {
-    Self(Box::new(move |s| (a, s)))
-  }
-}
-
-impl<'a, S, A: 'a> State<'a, S, A> {
-  pub fn and_then<B, F>(self, f: F) -> State<'a, S, B>
-  where
-    F: FnOnce(A) -> State<'a, S, B> + 'a,
-    S: 'static,
-  {
-    let h = self.0;
-    State(Box::new(move |s| {
-      let (a, newstate) = h(s);
-      let g = f(a).0;
-      g(newstate)
-    }))
-  }
-}
+pub type State<'a, S, A> = StateT<'a, S, A, Identity>;
 
 pub fn getst<'a, S: Clone + 'a>() -> State<'a, S, S> {
-  State(Box::new(|s| (s.clone(), s)))
+  statet::getst()
 }
 
 pub fn putst<'a, SNEW: 'a>(snew: SNEW) -> State<'a, SNEW, ()> {
-  State(Box::new(move |_| ((), snew)))
+  statet::putst(snew)
 }
 
 pub fn run_state<S, A>(s: S, ma: State<S, A>) -> (A, S) {
-  ma.0(s)
+  statet::run_statet(s, ma)
 }
 
 #[cfg(test)]
@@ -1,48 +1,27 @@
-use do_notation::Lift;
+use crate::statet::{self, EitherInner, StateT};
 
-#[allow(clippy::type_complexity)]
-pub struct StateEither<'a, S, A, E>(Box<dyn FnOnce(S) -> (S, Result<A, E>) + 'a>);
+pub type StateEither<'a, S, A, E> = StateT<'a, S, A, EitherInner<E>>;
 
-impl<'a, S, A: 'a, E> Lift<A> for StateEither<'a, S, A, E> {
-  fn lift(a: A) -> Self {
-    Self(Box::new(move |s| (s, Ok(a))))
-  }
-}
-
-impl<'a, S, A: 'a, E> StateEither<'a, S, A, E> {
-  pub fn and_then<B, F>(self, f: F) -> StateEither<'a, S, B, E>
-  where
-    F: FnOnce(A) -> StateEither<'a, S, B, E> + 'a,
-    S: 'a,
-    E: 'a,
-  {
-    StateEither(Box::new(move |s| match self.0(s) {
-      (newstate, Ok(a)) => f(a).0(newstate),
-      (s, Err(e)) => (s, Err(e)),
-    }))
-  }
-}
-
-pub fn getste<'a, S: Clone, E>() -> StateEither<'a, S, S, E> {
-  StateEither(Box::new(|s| (s.clone(), Ok(s))))
+pub fn getste<'a, S: Clone + 'a, E: 'a>() -> StateEither<'a, S, S, E> {
+  statet::getst()
 }
 
-pub fn putste<'a, SNEW: 'a, E>(snew: SNEW) -> StateEither<'a, SNEW, (), E> {
-  StateEither(Box::new(move |_| (snew, Ok(()))))
+pub fn putste<'a, SNEW: 'a, E: 'a>(snew: SNEW) -> StateEither<'a, SNEW, (), E> {
+  statet::putst(snew)
 }
 
-pub fn throwste<'a, S, A, E: 'a>(e: E) -> StateEither<'a, S, A, E> {
-  StateEither(Box::new(|s| (s, Err(e))))
+pub fn throwste<'a, S: 'a, A: 'a, E: 'a>(e: E) -> StateEither<'a, S, A, E> {
+  statet::throwst(e)
 }
 
 pub fn run_state_either<S, A, E>(s: S, ma: StateEither<S, A, E>) -> (S, Result<A, E>) {
-  ma.0(s)
+  statet::run_statet(s, ma)
 }
 
 #[cfg(test)]
 mod tests {
   use super::*;
-  use do_notation::m;
+  use do_notation::{m, Lift};
 
   fn helper<'a>(limit: i32) -> StateEither<'a, i32, i32, &'static str> {
     m! {
@@ -0,0 +1,255 @@
+use std::marker::PhantomData;
+
+use do_notation::Lift;
+
+/// Describes how a `StateT` thread its "inner" effect through a computation, i.e. what
+/// happens to the state when an action also needs to carry some additional effect (here:
+/// failure). `Identity` gives plain `State` (no extra effect), `ResultInner<E>` gives
+/// `StateT` stacked on top of `Result` (the state is discarded once an error occurs), and
+/// `EitherInner<E>` gives `ExceptT` stacked on top of `State` (the state is kept even on
+/// error). See [`state`](crate::state), [`stateresult`](crate::stateresult) and
+/// [`stateeither`](crate::stateeither) for the concrete monads built on top of these.
+pub trait MonadInner {
+  type Wrapped<S, A>;
+
+  fn pure<S, A>(s: S, a: A) -> Self::Wrapped<S, A>;
+
+  fn bind<S, A, B>(
+    wa: Self::Wrapped<S, A>,
+    f: impl FnOnce(S, A) -> Self::Wrapped<S, B>,
+  ) -> Self::Wrapped<S, B>;
+
+  fn map<S, A, B>(wa: Self::Wrapped<S, A>, f: impl FnOnce(A) -> B) -> Self::Wrapped<S, B>;
+}
+
+/// No extra effect: `Wrapped<S, A> = (A, S)`.
+pub struct Identity;
+
+impl MonadInner for Identity {
+  type Wrapped<S, A> = (A, S);
+
+  fn pure<S, A>(s: S, a: A) -> Self::Wrapped<S, A> {
+    (a, s)
+  }
+
+  fn bind<S, A, B>(
+    wa: Self::Wrapped<S, A>,
+    f: impl FnOnce(S, A) -> Self::Wrapped<S, B>,
+  ) -> Self::Wrapped<S, B> {
+    let (a, s) = wa;
+    f(s, a)
+  }
+
+  fn map<S, A, B>(wa: Self::Wrapped<S, A>, f: impl FnOnce(A) -> B) -> Self::Wrapped<S, B> {
+    let (a, s) = wa;
+    (f(a), s)
+  }
+}
+
+/// `StateT` over `Result`: `Wrapped<S, A> = Result<(S, A), E>`. The state at the point of
+/// failure is discarded, matching plain `Result`'s short-circuiting.
+pub struct ResultInner<E>(PhantomData<E>);
+
+impl<E> MonadInner for ResultInner<E> {
+  type Wrapped<S, A> = Result<(S, A), E>;
+
+  fn pure<S, A>(s: S, a: A) -> Self::Wrapped<S, A> {
+    Ok((s, a))
+  }
+
+  fn bind<S, A, B>(
+    wa: Self::Wrapped<S, A>,
+    f: impl FnOnce(S, A) -> Self::Wrapped<S, B>,
+  ) -> Self::Wrapped<S, B> {
+    match wa {
+      Ok((s, a)) => f(s, a),
+      Err(e) => Err(e),
+    }
+  }
+
+  fn map<S, A, B>(wa: Self::Wrapped<S, A>, f: impl FnOnce(A) -> B) -> Self::Wrapped<S, B> {
+    wa.map(|(s, a)| (s, f(a)))
+  }
+}
+
+/// `ExceptT` over `State`: `Wrapped<S, A> = (S, Result<A, E>)`. Unlike [`ResultInner`], the
+/// latest state is kept around even once an error occurs.
+pub struct EitherInner<E>(PhantomData<E>);
+
+impl<E> MonadInner for EitherInner<E> {
+  type Wrapped<S, A> = (S, Result<A, E>);
+
+  fn pure<S, A>(s: S, a: A) -> Self::Wrapped<S, A> {
+    (s, Ok(a))
+  }
+
+  fn bind<S, A, B>(
+    wa: Self::Wrapped<S, A>,
+    f: impl FnOnce(S, A) -> Self::Wrapped<S, B>,
+  ) -> Self::Wrapped<S, B> {
+    match wa {
+      (s, Ok(a)) => f(s, a),
+      (s, Err(e)) => (s, Err(e)),
+    }
+  }
+
+  fn map<S, A, B>(wa: Self::Wrapped<S, A>, f: impl FnOnce(A) -> B) -> Self::Wrapped<S, B> {
+    let (s, result) = wa;
+    (s, result.map(f))
+  }
+}
+
+/// A `MonadInner` that can also short-circuit with an error, independent of the state it
+/// was handed. Implemented by [`ResultInner`] and [`EitherInner`] but not [`Identity`],
+/// which has nothing to throw.
+pub trait MonadThrow<E>: MonadInner {
+  fn throw<S, A>(s: S, e: E) -> Self::Wrapped<S, A>;
+}
+
+impl<E> MonadThrow<E> for ResultInner<E> {
+  fn throw<S, A>(_s: S, e: E) -> Self::Wrapped<S, A> {
+    Err(e)
+  }
+}
+
+impl<E> MonadThrow<E> for EitherInner<E> {
+  fn throw<S, A>(s: S, e: E) -> Self::Wrapped<S, A> {
+    (s, Err(e))
+  }
+}
+
+/// The state monad transformer: `S -> M::Wrapped<S, A>`. `State`, `StateEither` and
+/// `StateResult` are all instances of this with a different choice of `M`.
+pub struct StateT<'a, S, A, M: MonadInner>(Box<dyn FnOnce(S) -> M::Wrapped<S, A> + 'a>);
+
+impl<'a, S, A: 'a, M: MonadInner> Lift<A> for StateT<'a, S, A, M> {
+  fn lift(a: A) -> Self {
+    Self(Box::new(move |s| M::pure(s, a)))
+  }
+}
+
+impl<'a, S, A, M: MonadInner> StateT<'a, S, A, M> {
+  pub fn and_then<B, F>(self, f: F) -> StateT<'a, S, B, M>
+  where
+    F: FnOnce(A) -> StateT<'a, S, B, M> + 'a,
+    A: 'a,
+    S: 'a,
+    M::Wrapped<S, A>: 'a,
+  {
+    StateT(Box::new(move |s| M::bind(self.0(s), move |s, a| f(a).0(s))))
+  }
+
+  // Implemented directly against `M::map` rather than `self.and_then(|a| Self::lift(f(a)))`,
+  // so it doesn't pay for a second boxed closure on top of the `bind`.
+  pub fn map<B, F>(self, f: F) -> StateT<'a, S, B, M>
+  where
+    F: FnOnce(A) -> B + 'a,
+    A: 'a,
+    S: 'a,
+    M::Wrapped<S, A>: 'a,
+  {
+    StateT(Box::new(move |s| M::map(self.0(s), f)))
+  }
+
+  // Runs `self` then `other` left-to-right, threading the state through both, and pairs up
+  // their results. Built directly on `M::bind`/`M::pure` rather than `and_then`.
+  pub fn zip2<B>(self, other: StateT<'a, S, B, M>) -> StateT<'a, S, (A, B), M>
+  where
+    A: 'a,
+    B: 'a,
+    S: 'a,
+    M::Wrapped<S, A>: 'a,
+    M::Wrapped<S, B>: 'a,
+  {
+    StateT(Box::new(move |s| {
+      M::bind(self.0(s), move |s, a| {
+        M::bind(other.0(s), move |s, b| M::pure(s, (a, b)))
+      })
+    }))
+  }
+}
+
+/// Runs a vector of actions left-to-right, threading the state/error through each, and
+/// collects their results into a single action producing a `Vec`.
+pub fn sequence<'a, S, A, M>(mas: Vec<StateT<'a, S, A, M>>) -> StateT<'a, S, Vec<A>, M>
+where
+  S: 'a,
+  A: 'a,
+  M: MonadInner + 'a,
+  M::Wrapped<S, A>: 'a,
+  M::Wrapped<S, Vec<A>>: 'a,
+{
+  mas.into_iter().fold(StateT::lift(Vec::new()), |acc, ma| {
+    acc.zip2(ma).map(|(mut v, a)| {
+      v.push(a);
+      v
+    })
+  })
+}
+
+/// Maps `f` over `items` and [`sequence`]s the resulting actions.
+pub fn traverse<'a, S, T, A, M, F>(items: Vec<T>, f: F) -> StateT<'a, S, Vec<A>, M>
+where
+  S: 'a,
+  T: 'a,
+  A: 'a,
+  M: MonadInner + 'a,
+  M::Wrapped<S, A>: 'a,
+  M::Wrapped<S, Vec<A>>: 'a,
+  F: Fn(T) -> StateT<'a, S, A, M>,
+{
+  sequence(items.into_iter().map(f).collect())
+}
+
+pub fn statet<'a, S, A, M, F>(f: F) -> StateT<'a, S, A, M>
+where
+  M: MonadInner,
+  F: FnOnce(S) -> M::Wrapped<S, A> + 'a,
+{
+  StateT(Box::new(f))
+}
+
+pub fn getst<'a, S: Clone + 'a, M: MonadInner + 'a>() -> StateT<'a, S, S, M> {
+  StateT(Box::new(|s: S| M::pure(s.clone(), s)))
+}
+
+pub fn putst<'a, SNEW: 'a, M: MonadInner + 'a>(snew: SNEW) -> StateT<'a, SNEW, (), M> {
+  StateT(Box::new(move |_| M::pure(snew, ())))
+}
+
+pub fn throwst<'a, S: 'a, A: 'a, E: 'a, M: MonadThrow<E> + 'a>(e: E) -> StateT<'a, S, A, M> {
+  StateT(Box::new(move |s| M::throw(s, e)))
+}
+
+pub fn run_statet<S, A, M: MonadInner>(s: S, ma: StateT<S, A, M>) -> M::Wrapped<S, A> {
+  ma.0(s)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn map_transforms_value() {
+    let ma: StateT<i32, i32, Identity> = getst().map(|st| st + 1);
+    assert_eq!((11, 10), run_statet(10, ma));
+  }
+
+  #[test]
+  fn zip2_pairs_and_threads_state() {
+    let ma: StateT<i32, _, Identity> = getst().zip2(putst(99).and_then(|_| getst()));
+    assert_eq!(((10, 99), 99), run_statet(10, ma));
+  }
+
+  #[test]
+  fn sequence_collects_in_order() {
+    let ma: StateT<i32, _, Identity> = sequence(vec![getst(), getst(), getst()]);
+    assert_eq!((vec![10, 10, 10], 10), run_statet(10, ma));
+  }
+
+  #[test]
+  fn traverse_maps_then_sequences() {
+    let ma: StateT<i32, _, Identity> = traverse(vec![1, 2, 3], |n| getst().map(move |st| st + n));
+    assert_eq!((vec![11, 12, 13], 10), run_statet(10, ma));
+  }
+}